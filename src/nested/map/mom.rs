@@ -6,7 +6,9 @@
 
 use std::{
   cmp::Ordering,
+  collections::HashMap,
   fmt::{Debug, Display},
+  io::{self, Read, Write},
   mem,
   iter::Map,
   slice::Iter,
@@ -14,7 +16,7 @@ use std::{
 
 use num::PrimInt;
 
-use crate::nested::map::skymap::SkyMapValue;
+use crate::nested::map::skymap::{SkyMap, SkyMapValue};
 
 
 /// `ZUniqHHashT` stands for `Z-curve ordered Uniq Healpix Hash Type`.
@@ -89,6 +91,41 @@ pub trait ZUniqHashT:
     }
   }
 
+  /// Transforms a `depth` and `hash value` tuple into the IVOA MOC `UNIQ` pixel number
+  /// (`uniq = 4^(depth + 1) + hash`), as opposed to this crate's internal, sort-friendly `zuniq`.
+  fn to_uniq(depth: u8, hash: Self) -> Self {
+    hash + Self::one().unsigned_shl(((depth as u32) + 1) << 1)
+  }
+
+  /// Returns the `depth` and `hash value` this IVOA MOC `UNIQ` pixel number contains.
+  fn from_uniq(uniq: Self) -> (u8, Self) {
+    let n_bits = Self::N_BITS as u32 - uniq.leading_zeros();
+    let depth = (((n_bits - 1) >> 1) as u8) - 1;
+    let hash = uniq - Self::one().unsigned_shl(((depth as u32) + 1) << 1);
+    (depth, hash)
+  }
+
+  /// Big-endian serialization of `self` on `Self::N_BYTES` bytes, as used in e.g. FITS files.
+  fn to_be_bytes_vec(self) -> Vec<u8> {
+    let n = Self::N_BYTES as usize;
+    let mut bytes = vec![0u8; n];
+    let mut v = self;
+    let byte_mask = Self::from(0xffu8).unwrap();
+    for byte in bytes.iter_mut().rev() {
+      *byte = (v & byte_mask).to_u8().unwrap();
+      v = v.unsigned_shr(8);
+    }
+    bytes
+  }
+
+  /// Reverse of `to_be_bytes_vec`: reads a big-endian encoded value from `bytes`
+  /// (which must contain exactly `Self::N_BYTES` bytes).
+  fn from_be_bytes_slice(bytes: &[u8]) -> Self {
+    bytes.iter().fold(Self::zero(), |v, &byte| {
+      v.unsigned_shl(8) | Self::from(byte).unwrap()
+    })
+  }
+
 }
 
 impl ZUniqHashT for u32 {}
@@ -154,11 +191,14 @@ pub trait Mom<'a> {
     let mut it = self.zuniqs();
     if let Some(mut l) = it.next() {
       let (mut depth_l, mut hash_l) = Self::ZUniqHType::from_zuniq(l);
+      if depth_l > self.depth_max() {
+        return Err(format!("Element has a larger depth than MOM maximum depth. Elem: {}; Depth: {}; Mom max depth: {}", l, depth_l, self.depth_max()));
+      }
       for r in it {
-        if depth_l < self.depth_max() {
-          return Err(format!("Element has a larger depth than MOM maximum depth. Elem: {}; Depth: {}; Mom max depth: {}", l, depth_l, self.depth_max()));
-        }
         let (depth_r, hash_r) =  Self::ZUniqHType::from_zuniq(r);
+        if depth_r > self.depth_max() {
+          return Err(format!("Element has a larger depth than MOM maximum depth. Elem: {}; Depth: {}; Mom max depth: {}", r, depth_r, self.depth_max()));
+        }
         if l >= r {
           return Err(format!("The MOM is not ordered: {} >= {}", l, r));
         } else if Self::ZUniqHType::are_overlapping_cells(depth_l, hash_l, depth_r, hash_r) {
@@ -172,6 +212,61 @@ pub trait Mom<'a> {
     Ok(())
   }
 
+  /// Returns a new MOM being the union of `self` and `rhs`.
+  /// Cells present in a single input are kept as-is (the `merge` closure is still called, with
+  /// `None` on the side missing the cell, so it can decide e.g. to simply clone the other value).
+  /// Cells present in both inputs are combined using `merge`.
+  fn or<T, F>(&'a self, rhs: &'a T, mut merge: F) -> MomVecImpl<Self::ZUniqHType, Self::ValueType>
+    where
+      T: Mom<'a, ZUniqHType = Self::ZUniqHType, ValueType = Self::ValueType>,
+      F: FnMut(Option<&Self::ValueType>, Option<&Self::ValueType>) -> Self::ValueType,
+      Self::ValueType: Clone,
+  {
+    combine(self, rhs, move |l, r| Some(merge(l, r)))
+  }
+
+  /// Returns a new MOM being the intersection of `self` and `rhs`.
+  /// Only cells overlapped by both inputs are kept, their values combined using `merge`.
+  fn and<T, F>(&'a self, rhs: &'a T, mut merge: F) -> MomVecImpl<Self::ZUniqHType, Self::ValueType>
+    where
+      T: Mom<'a, ZUniqHType = Self::ZUniqHType, ValueType = Self::ValueType>,
+      F: FnMut(Option<&Self::ValueType>, Option<&Self::ValueType>) -> Self::ValueType,
+      Self::ValueType: Clone,
+  {
+    combine(self, rhs, move |l, r| match (l, r) {
+      (Some(_), Some(_)) => Some(merge(l, r)),
+      _ => None,
+    })
+  }
+
+  /// Returns a new MOM being `self` minus `rhs`, i.e. the cells of `self` not overlapped by `rhs`.
+  /// Surviving cells keep the value they have in `self`.
+  fn not<T>(&'a self, rhs: &'a T) -> MomVecImpl<Self::ZUniqHType, Self::ValueType>
+    where
+      T: Mom<'a, ZUniqHType = Self::ZUniqHType, ValueType = Self::ValueType>,
+      Self::ValueType: Clone,
+  {
+    combine(self, rhs, |l, r| match (l, r) {
+      (Some(lv), None) => Some(lv.clone()),
+      _ => None,
+    })
+  }
+
+  /// Returns a new MOM being the symmetric difference of `self` and `rhs`, i.e. the cells
+  /// overlapped by exactly one of the two inputs. Surviving cells keep the value they have in
+  /// the input that overlaps them.
+  fn xor<T>(&'a self, rhs: &'a T) -> MomVecImpl<Self::ZUniqHType, Self::ValueType>
+    where
+      T: Mom<'a, ZUniqHType = Self::ZUniqHType, ValueType = Self::ValueType>,
+      Self::ValueType: Clone,
+  {
+    combine(self, rhs, |l, r| match (l, r) {
+      (Some(lv), None) => Some(lv.clone()),
+      (None, Some(rv)) => Some(rv.clone()),
+      _ => None,
+    })
+  }
+
 }
 
 /// Implementation of a MOM in a simple vector.
@@ -183,6 +278,17 @@ pub struct MomVecImpl<Z, V>
   depth: u8,
   entries: Vec<(Z, V)>,
 }
+impl<Z, V> MomVecImpl<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue
+{
+  /// Creates a new MOM from `entries`, already sorted by `zuniq` and non-overlapping,
+  /// without checking those properties (see `Mom::check_is_mom`).
+  pub fn new_unchecked(depth_max: u8, entries: Vec<(Z, V)>) -> Self {
+    Self { depth: depth_max, entries }
+  }
+}
 impl<'a, Z, V> Mom<'a> for MomVecImpl<Z, V>
   where
     Z: ZUniqHashT,
@@ -198,23 +304,437 @@ impl<'a, Z, V> Mom<'a> for MomVecImpl<Z, V>
   }
 
   fn get_cell_containing_unsafe(&'a self, hash_at_depth_max: Self::ZUniqHType) -> Option<(Self::ZUniqHType, &'a Self::ValueType)> {
-    match self.entries.binary_search_by(|&(z, _)| z.cmp(&hash_at_depth_max)) {
+    slice_get_cell_containing_unsafe(&self.entries, hash_at_depth_max)
+  }
+
+  fn get_overlapped_cells(&'a self, zuniq: Self::ZUniqHType) -> Vec<(Self::ZUniqHType, &'a Self::ValueType)> {
+    slice_get_overlapped_cells(&self.entries, zuniq)
+  }
+
+  fn zuniqs(&'a self) -> Self::ZuniqIt {
+    self.entries.iter().map(|&(zuniq, _)| zuniq)
+  }
+
+  fn entries(&'a self) -> Self::EntriesIt {
+    self.entries.iter().map(|(z, v)| (*z, v))
+  }
+}
+
+/// Looks up the (sorted, non-overlapping) cell of `entries` containing `hash_at_depth_max`,
+/// shared between `MomVecImpl` and `MomSliceImpl`.
+fn slice_get_cell_containing_unsafe<Z, V>(entries: &[(Z, V)], hash_at_depth_max: Z) -> Option<(Z, &V)>
+  where
+    Z: ZUniqHashT,
+{
+  match entries.binary_search_by(|&(z, _)| z.cmp(&hash_at_depth_max)) {
+    Ok(i) => {
+      let e = &entries[i];
+      Some((e.0, &e.1))
+    },
+    Err(i) => {
+      if i > 0 {
+        // if array len is 0, i will be 0 so we do not enter here.
+        let e = &entries[i - 1];
+        if Z::are_overlapping(hash_at_depth_max, e.0) {
+          return Some((e.0, &e.1));
+        }
+      }
+      if i < entries.len() {
+        let e = &entries[i];
+        if Z::are_overlapping(hash_at_depth_max, e.0) {
+          return Some((e.0, &e.1));
+        }
+      }
+      None
+    }
+  }
+}
+
+/// Returns all cells of `entries` (sorted, non-overlapping) overlapping `zuniq`, shared between
+/// `MomVecImpl` and `MomSliceImpl`.
+///
+/// `range.start` is a `usize`: `range.start > 0` is checked before computing `range.start - 1`,
+/// so walking left never underflows, and the entry at index 0 is still examined.
+fn slice_get_overlapped_cells<Z, V>(entries: &[(Z, V)], zuniq: Z) -> Vec<(Z, &V)>
+  where
+    Z: ZUniqHashT,
+{
+  let mut range = match entries.binary_search_by(|&(z, _)| z.cmp(&zuniq)) {
+    Ok(i) => i..i + 1,
+    Err(i) => i..i,
+  };
+  while range.start > 0 && Z::are_overlapping(zuniq, entries[range.start - 1].0) {
+    range.start -= 1;
+  }
+  while range.end < entries.len() && Z::are_overlapping(zuniq, entries[range.end].0) {
+    range.end += 1;
+  }
+  range.map(|i| {
+    let (z, v) = &entries[i];
+    (*z, v)
+  }).collect()
+}
+
+/// Zero-copy implementation of a MOM borrowing its (already sorted, non-overlapping) entries
+/// from a `&'a [(Z, V)]` slice, sharing its query logic with `MomVecImpl`.
+pub struct MomSliceImpl<'a, Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue
+{
+  depth: u8,
+  entries: &'a [(Z, V)],
+}
+impl<'a, Z, V> MomSliceImpl<'a, Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue
+{
+  /// Wraps `entries`, already sorted by `zuniq` and non-overlapping, without checking those
+  /// properties (see `Mom::check_is_mom`).
+  pub fn new_unchecked(depth_max: u8, entries: &'a [(Z, V)]) -> Self {
+    Self { depth: depth_max, entries }
+  }
+}
+impl<'a, Z, V> Mom<'a> for MomSliceImpl<'a, Z, V>
+  where
+    Z: ZUniqHashT,
+    V: 'a + SkyMapValue
+{
+  type ZUniqHType = Z;
+  type ValueType = V;
+  type ZuniqIt = Map<Iter<'a, (Z, V)>, fn(&'a (Z, V)) -> Z>;
+  type EntriesIt = Map<Iter<'a, (Z, V)>, fn(&'a (Z, V)) -> (Z, &'a V)>;
+
+  fn depth_max(&self) -> u8 {
+    self.depth
+  }
+
+  fn get_cell_containing_unsafe(&'a self, hash_at_depth_max: Self::ZUniqHType) -> Option<(Self::ZUniqHType, &'a Self::ValueType)> {
+    slice_get_cell_containing_unsafe(self.entries, hash_at_depth_max)
+  }
+
+  fn get_overlapped_cells(&'a self, zuniq: Self::ZUniqHType) -> Vec<(Self::ZUniqHType, &'a Self::ValueType)> {
+    slice_get_overlapped_cells(self.entries, zuniq)
+  }
+
+  fn zuniqs(&'a self) -> Self::ZuniqIt {
+    self.entries.iter().map(|&(zuniq, _)| zuniq)
+  }
+
+  fn entries(&'a self) -> Self::EntriesIt {
+    self.entries.iter().map(|(z, v)| (*z, v))
+  }
+}
+
+/// Turns `mom`'s entries into sorted, non-overlapping, half-open hash intervals `[start, end)`
+/// expressed at `depth_max` (which must be `>= mom.depth_max()`).
+fn to_intervals<'a, Z, V, M>(mom: &'a M, depth_max: u8) -> Vec<(Z, Z, &'a V)>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue + 'a,
+    M: Mom<'a, ZUniqHType = Z, ValueType = V> + ?Sized,
+{
+  mom.entries().map(|(zuniq, v)| {
+    let (depth, hash) = Z::from_zuniq(zuniq);
+    let shift = Z::shift(depth_max - depth);
+    let start = hash.unsigned_shl(shift as u32);
+    let end = start + Z::one().unsigned_shl(shift as u32);
+    (start, end, v)
+  }).collect()
+}
+
+/// Decomposes the half-open hash interval `[start, end)`, expressed at `depth_max`, into the
+/// largest possible aligned HEALPix cells, pushing their `zuniq` values (in increasing order)
+/// into `zuniqs`.
+fn append_zuniqs_for_range<Z: ZUniqHashT>(depth_max: u8, mut start: Z, end: Z, zuniqs: &mut Vec<Z>) {
+  while start < end {
+    let max_delta_depth_from_alignment = if start == Z::zero() {
+      depth_max
+    } else {
+      (start.trailing_zeros() as u8 / Z::DIM).min(depth_max)
+    };
+    let mut delta_depth = max_delta_depth_from_alignment;
+    while start + Z::one().unsigned_shl(Z::shift(delta_depth) as u32) > end {
+      delta_depth -= 1;
+    }
+    let len = Z::one().unsigned_shl(Z::shift(delta_depth) as u32);
+    let depth = depth_max - delta_depth;
+    let hash = start.unsigned_shr(Z::shift(delta_depth) as u32);
+    zuniqs.push(Z::to_zuniq(depth, hash));
+    start = start + len;
+  }
+}
+
+/// Two-pointer sweep of two sorted, non-overlapping interval lists, calling `op` on every
+/// maximal sub-interval over which the pair of (left, right) values overlapped stays constant.
+/// A `None` side means that input has no cell covering the current position.
+/// `op` returning `None` drops the sub-interval from the result.
+fn sweep<Z, V, F>(left: &[(Z, Z, &V)], right: &[(Z, Z, &V)], mut op: F) -> Vec<(Z, Z, V)>
+  where
+    Z: ZUniqHashT,
+    F: FnMut(Option<&V>, Option<&V>) -> Option<V>,
+{
+  let mut out = Vec::new();
+  let mut li = 0usize;
+  let mut ri = 0usize;
+  let mut lcur = left.first().copied();
+  let mut rcur = right.first().copied();
+  while lcur.is_some() || rcur.is_some() {
+    let seg_start = match (lcur, rcur) {
+      (Some((ls, _, _)), Some((rs, _, _))) => ls.min(rs),
+      (Some((ls, _, _)), None) => ls,
+      (None, Some((rs, _, _))) => rs,
+      (None, None) => unreachable!(),
+    };
+    let l_active = matches!(lcur, Some((ls, _, _)) if ls == seg_start);
+    let r_active = matches!(rcur, Some((rs, _, _)) if rs == seg_start);
+    let mut seg_end = match (lcur, rcur) {
+      (Some((_, le, _)), Some((_, re, _))) => le.min(re),
+      (Some((_, le, _)), None) => le,
+      (None, Some((_, re, _))) => re,
+      (None, None) => unreachable!(),
+    };
+    if !l_active {
+      seg_end = lcur.map_or(seg_end, |(ls, _, _)| seg_end.min(ls));
+    }
+    if !r_active {
+      seg_end = rcur.map_or(seg_end, |(rs, _, _)| seg_end.min(rs));
+    }
+    let lval = if l_active { lcur.map(|(_, _, v)| v) } else { None };
+    let rval = if r_active { rcur.map(|(_, _, v)| v) } else { None };
+    if let Some(v) = op(lval, rval) {
+      out.push((seg_start, seg_end, v));
+    }
+    if l_active {
+      let (_, le, lv) = lcur.unwrap();
+      lcur = if seg_end < le {
+        Some((seg_end, le, lv))
+      } else {
+        li += 1;
+        left.get(li).copied()
+      };
+    }
+    if r_active {
+      let (_, re, rv) = rcur.unwrap();
+      rcur = if seg_end < re {
+        Some((seg_end, re, rv))
+      } else {
+        ri += 1;
+        right.get(ri).copied()
+      };
+    }
+  }
+  out
+}
+
+/// Combines two MOMs range-wise: normalizes both to `depth_max = max(lhs.depth_max(), rhs.depth_max())`,
+/// sweeps their hash intervals and re-encodes the surviving runs (as decided by `op`) back to `zuniq`.
+fn combine<'a, Z, V, M1, M2, F>(lhs: &'a M1, rhs: &'a M2, op: F) -> MomVecImpl<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue + Clone + 'a,
+    M1: Mom<'a, ZUniqHType = Z, ValueType = V> + ?Sized,
+    M2: Mom<'a, ZUniqHType = Z, ValueType = V> + ?Sized,
+    F: FnMut(Option<&V>, Option<&V>) -> Option<V>,
+{
+  let depth_max = lhs.depth_max().max(rhs.depth_max());
+  let left = to_intervals(lhs, depth_max);
+  let right = to_intervals(rhs, depth_max);
+  let runs = sweep(&left, &right, op);
+  let mut entries = Vec::new();
+  for (start, end, value) in runs {
+    let mut zuniqs = Vec::new();
+    append_zuniqs_for_range(depth_max, start, end, &mut zuniqs);
+    entries.extend(zuniqs.into_iter().map(|zuniq| (zuniq, value.clone())));
+  }
+  MomVecImpl::new_unchecked(depth_max, entries)
+}
+
+/// Coverage flag of a `FlaggedMom` cell, mirroring the `cdshealpix` BMOC: whether the cell is
+/// entirely inside the region it approximates (`Full`) or only overlaps its boundary (`Partial`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+  Full,
+  Partial,
+}
+
+impl Flag {
+  /// `Full` only if both `self` and `other` are `Full`, `Partial` as soon as one of them is not.
+  /// Used both to combine two overlapping cells (intersection/union) and to degrade a coarser
+  /// cell made of several finer ones (`Full` only if all of them were `Full`).
+  fn both_full(self, other: Self) -> Self {
+    match (self, other) {
+      (Flag::Full, Flag::Full) => Flag::Full,
+      _ => Flag::Partial,
+    }
+  }
+}
+
+/// A `Mom` additionally tagging each cell `Flag::Full` or `Flag::Partial`, so it can represent
+/// approximate geometry the way the `cdshealpix` BMOC does (e.g. "is this point definitely
+/// inside the region?" vs. "does this point possibly overlap the region?").
+pub trait FlaggedMom<'a>: Mom<'a> {
+  /// Type of iterator iterating on all (sorted!) flagged entries.
+  type FlaggedEntriesIt: Iterator<Item = (Self::ZUniqHType, &'a Self::ValueType, Flag)>;
+
+  /// Returns all entries, i.e. HEALPix zuniq hash / value / flag tuples, following the z-order
+  /// curve order.
+  fn entries_flagged(&'a self) -> Self::FlaggedEntriesIt;
+
+  /// Same as `Mom::get_cell_containing_unsafe`, additionally returning the cell's `Flag`.
+  fn get_cell_containing_flagged_unsafe(&'a self, hash_at_depth_max: Self::ZUniqHType) -> Option<(Self::ZUniqHType, &'a Self::ValueType, Flag)>;
+
+  /// Same as `Mom::get_overlapped_cells`, additionally returning each cell's `Flag`.
+  fn get_overlapped_cells_flagged(&'a self, zuniq: Self::ZUniqHType) -> Vec<(Self::ZUniqHType, &'a Self::ValueType, Flag)>;
+
+  /// Same as `Mom::get_cell_containing`, additionally returning the cell's `Flag`.
+  fn get_cell_containing_flagged(&'a self, zuniq_at_depth_max: Self::ZUniqHType) -> Result<Option<(Self::ZUniqHType, &'a Self::ValueType, Flag)>, String> {
+    self.check_zuniq_depth_is_depth_max(zuniq_at_depth_max)
+      .map(|_| self.get_cell_containing_flagged_unsafe(zuniq_at_depth_max))
+  }
+
+  /// Returns a new flagged MOM being the union of `self` and `rhs`. A cell present in both
+  /// inputs is `Flag::Full` only if both were `Full`; a cell present in a single input keeps its
+  /// flag as-is.
+  fn or_flagged<T, F>(&'a self, rhs: &'a T, mut merge: F) -> MomVecImplFlagged<Self::ZUniqHType, Self::ValueType>
+    where
+      T: FlaggedMom<'a, ZUniqHType = Self::ZUniqHType, ValueType = Self::ValueType>,
+      F: FnMut(Option<&Self::ValueType>, Option<&Self::ValueType>) -> Self::ValueType,
+      Self::ValueType: Clone,
+  {
+    combine_flagged(self, rhs, move |l, r| {
+      let value = merge(l.map(|(v, _)| v), r.map(|(v, _)| v));
+      let flag = match (l, r) {
+        (Some((_, lf)), Some((_, rf))) => lf.both_full(rf),
+        (Some((_, f)), None) | (None, Some((_, f))) => f,
+        (None, None) => unreachable!(),
+      };
+      Some((value, flag))
+    })
+  }
+
+  /// Returns a new flagged MOM being the intersection of `self` and `rhs`; a surviving cell is
+  /// `Flag::Full` only if it was `Full` on both sides.
+  fn and_flagged<T, F>(&'a self, rhs: &'a T, mut merge: F) -> MomVecImplFlagged<Self::ZUniqHType, Self::ValueType>
+    where
+      T: FlaggedMom<'a, ZUniqHType = Self::ZUniqHType, ValueType = Self::ValueType>,
+      F: FnMut(Option<&Self::ValueType>, Option<&Self::ValueType>) -> Self::ValueType,
+      Self::ValueType: Clone,
+  {
+    combine_flagged(self, rhs, move |l, r| match (l, r) {
+      (Some((lv, lf)), Some((rv, rf))) => Some((merge(Some(lv), Some(rv)), lf.both_full(rf))),
+      _ => None,
+    })
+  }
+
+  /// Returns a new flagged MOM being `self` minus `rhs`; surviving cells keep their value and
+  /// flag from `self`.
+  fn not_flagged<T>(&'a self, rhs: &'a T) -> MomVecImplFlagged<Self::ZUniqHType, Self::ValueType>
+    where
+      T: FlaggedMom<'a, ZUniqHType = Self::ZUniqHType, ValueType = Self::ValueType>,
+      Self::ValueType: Clone,
+  {
+    combine_flagged(self, rhs, |l, r| match (l, r) {
+      (Some((lv, lf)), None) => Some((lv.clone(), lf)),
+      _ => None,
+    })
+  }
+
+  /// Returns a new flagged MOM being the symmetric difference of `self` and `rhs`; surviving
+  /// cells keep their value and flag from whichever input overlapped them.
+  fn xor_flagged<T>(&'a self, rhs: &'a T) -> MomVecImplFlagged<Self::ZUniqHType, Self::ValueType>
+    where
+      T: FlaggedMom<'a, ZUniqHType = Self::ZUniqHType, ValueType = Self::ValueType>,
+      Self::ValueType: Clone,
+  {
+    combine_flagged(self, rhs, |l, r| match (l, r) {
+      (Some((lv, lf)), None) => Some((lv.clone(), lf)),
+      (None, Some((rv, rf))) => Some((rv.clone(), rf)),
+      _ => None,
+    })
+  }
+
+}
+
+/// Implementation of a `FlaggedMom` in a simple vector.
+pub struct MomVecImplFlagged<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue
+{
+  depth: u8,
+  entries: Vec<(Z, V, Flag)>,
+}
+impl<Z, V> MomVecImplFlagged<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue
+{
+  /// Creates a new flagged MOM from `entries`, already sorted by `zuniq` and non-overlapping,
+  /// without checking those properties (see `Mom::check_is_mom`).
+  pub fn new_unchecked(depth_max: u8, entries: Vec<(Z, V, Flag)>) -> Self {
+    Self { depth: depth_max, entries }
+  }
+}
+impl<'a, Z, V> Mom<'a> for MomVecImplFlagged<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: 'a + SkyMapValue
+{
+  type ZUniqHType = Z;
+  type ValueType = V;
+  type ZuniqIt = Map<Iter<'a, (Z, V, Flag)>, fn(&'a (Z, V, Flag)) -> Z>;
+  type EntriesIt = Map<Iter<'a, (Z, V, Flag)>, fn(&'a (Z, V, Flag)) -> (Z, &'a V)>;
+
+  fn depth_max(&self) -> u8 {
+    self.depth
+  }
+
+  fn get_cell_containing_unsafe(&'a self, hash_at_depth_max: Self::ZUniqHType) -> Option<(Self::ZUniqHType, &'a Self::ValueType)> {
+    self.get_cell_containing_flagged_unsafe(hash_at_depth_max).map(|(z, v, _)| (z, v))
+  }
+
+  fn get_overlapped_cells(&'a self, zuniq: Self::ZUniqHType) -> Vec<(Self::ZUniqHType, &'a Self::ValueType)> {
+    self.get_overlapped_cells_flagged(zuniq).into_iter().map(|(z, v, _)| (z, v)).collect()
+  }
+
+  fn zuniqs(&'a self) -> Self::ZuniqIt {
+    self.entries.iter().map(|&(zuniq, _, _)| zuniq)
+  }
+
+  fn entries(&'a self) -> Self::EntriesIt {
+    self.entries.iter().map(|(z, v, _)| (*z, v))
+  }
+}
+impl<'a, Z, V> FlaggedMom<'a> for MomVecImplFlagged<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: 'a + SkyMapValue
+{
+  type FlaggedEntriesIt = Map<Iter<'a, (Z, V, Flag)>, fn(&'a (Z, V, Flag)) -> (Z, &'a V, Flag)>;
+
+  fn entries_flagged(&'a self) -> Self::FlaggedEntriesIt {
+    self.entries.iter().map(|(z, v, f)| (*z, v, *f))
+  }
+
+  fn get_cell_containing_flagged_unsafe(&'a self, hash_at_depth_max: Self::ZUniqHType) -> Option<(Self::ZUniqHType, &'a Self::ValueType, Flag)> {
+    match self.entries.binary_search_by(|&(z, _, _)| z.cmp(&hash_at_depth_max)) {
       Ok(i) => {
         let e = &self.entries[i];
-        Some((e.0, &e.1))
+        Some((e.0, &e.1, e.2))
       },
       Err(i) => {
         if i > 0 {
-          // if array len is 0, i will be 0 so we do not enter here.
           let e = &self.entries[i - 1];
           if Z::are_overlapping(hash_at_depth_max, e.0) {
-            return Some((e.0, &e.1));
+            return Some((e.0, &e.1, e.2));
           }
         }
         if i < self.entries.len() {
           let e = &self.entries[i];
           if Z::are_overlapping(hash_at_depth_max, e.0) {
-            return Some((e.0, &e.1));
+            return Some((e.0, &e.1, e.2));
           }
         }
         None
@@ -222,47 +742,991 @@ impl<'a, Z, V> Mom<'a> for MomVecImpl<Z, V>
     }
   }
 
-  fn get_overlapped_cells(&'a self, zuniq: Self::ZUniqHType) -> Vec<(Self::ZUniqHType, &'a Self::ValueType)> {
-    let mut range = match self.entries.binary_search_by(|&(z, _)| z.cmp(&zuniq)) {
+  fn get_overlapped_cells_flagged(&'a self, zuniq: Self::ZUniqHType) -> Vec<(Self::ZUniqHType, &'a Self::ValueType, Flag)> {
+    let mut range = match self.entries.binary_search_by(|&(z, _, _)| z.cmp(&zuniq)) {
       Ok(i) => i..i + 1,
       Err(i) => i..i,
     };
-    while range.start - 1 > 0 &&  Z::are_overlapping(zuniq, self.entries[range.start - 1].0) {
+    while range.start > 0 && Z::are_overlapping(zuniq, self.entries[range.start - 1].0) {
       range.start -= 1;
     }
-    while range.end < self.entries.len() && Z::are_overlapping(zuniq, self.entries[range.end].0)  {
+    while range.end < self.entries.len() && Z::are_overlapping(zuniq, self.entries[range.end].0) {
       range.end += 1;
     }
     range.into_iter().map(|i| {
-      let (z, v) = &self.entries[i];
-      (*z, v)
+      let (z, v, f) = &self.entries[i];
+      (*z, v, *f)
     }).collect()
   }
+}
 
-  fn zuniqs(&'a self) -> Self::ZuniqIt {
-    self.entries.iter().map(|&(zuniq, _)| zuniq)
+/// Turns `mom`'s flagged entries into sorted, non-overlapping, half-open hash intervals
+/// `[start, end)` expressed at `depth_max` (which must be `>= mom.depth_max()`).
+fn to_intervals_flagged<'a, Z, V, M>(mom: &'a M, depth_max: u8) -> Vec<(Z, Z, &'a V, Flag)>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue + 'a,
+    M: FlaggedMom<'a, ZUniqHType = Z, ValueType = V> + ?Sized,
+{
+  mom.entries_flagged().map(|(zuniq, v, flag)| {
+    let (depth, hash) = Z::from_zuniq(zuniq);
+    let shift = Z::shift(depth_max - depth);
+    let start = hash.unsigned_shl(shift as u32);
+    let end = start + Z::one().unsigned_shl(shift as u32);
+    (start, end, v, flag)
+  }).collect()
+}
+
+/// Same sweep as `sweep`, additionally threading a `Flag` alongside each side's value.
+fn sweep_flagged<Z, V, F>(
+  left: &[(Z, Z, &V, Flag)],
+  right: &[(Z, Z, &V, Flag)],
+  mut op: F,
+) -> Vec<(Z, Z, V, Flag)>
+  where
+    Z: ZUniqHashT,
+    F: FnMut(Option<(&V, Flag)>, Option<(&V, Flag)>) -> Option<(V, Flag)>,
+{
+  let mut out = Vec::new();
+  let mut li = 0usize;
+  let mut ri = 0usize;
+  let mut lcur = left.first().copied();
+  let mut rcur = right.first().copied();
+  while lcur.is_some() || rcur.is_some() {
+    let seg_start = match (lcur, rcur) {
+      (Some((ls, _, _, _)), Some((rs, _, _, _))) => ls.min(rs),
+      (Some((ls, _, _, _)), None) => ls,
+      (None, Some((rs, _, _, _))) => rs,
+      (None, None) => unreachable!(),
+    };
+    let l_active = matches!(lcur, Some((ls, _, _, _)) if ls == seg_start);
+    let r_active = matches!(rcur, Some((rs, _, _, _)) if rs == seg_start);
+    let mut seg_end = match (lcur, rcur) {
+      (Some((_, le, _, _)), Some((_, re, _, _))) => le.min(re),
+      (Some((_, le, _, _)), None) => le,
+      (None, Some((_, re, _, _))) => re,
+      (None, None) => unreachable!(),
+    };
+    if !l_active {
+      seg_end = lcur.map_or(seg_end, |(ls, _, _, _)| seg_end.min(ls));
+    }
+    if !r_active {
+      seg_end = rcur.map_or(seg_end, |(rs, _, _, _)| seg_end.min(rs));
+    }
+    let lval = if l_active { lcur.map(|(_, _, v, f)| (v, f)) } else { None };
+    let rval = if r_active { rcur.map(|(_, _, v, f)| (v, f)) } else { None };
+    if let Some(v) = op(lval, rval) {
+      out.push((seg_start, seg_end, v.0, v.1));
+    }
+    if l_active {
+      let (_, le, lv, lf) = lcur.unwrap();
+      lcur = if seg_end < le {
+        Some((seg_end, le, lv, lf))
+      } else {
+        li += 1;
+        left.get(li).copied()
+      };
+    }
+    if r_active {
+      let (_, re, rv, rf) = rcur.unwrap();
+      rcur = if seg_end < re {
+        Some((seg_end, re, rv, rf))
+      } else {
+        ri += 1;
+        right.get(ri).copied()
+      };
+    }
   }
+  out
+}
 
-  fn entries(&'a self) -> Self::EntriesIt {
-    self.entries.iter().map(|(z, v)| (*z, v))
+/// Same as `combine`, for two `FlaggedMom`s, producing a `MomVecImplFlagged`.
+fn combine_flagged<'a, Z, V, M1, M2, F>(lhs: &'a M1, rhs: &'a M2, op: F) -> MomVecImplFlagged<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue + Clone + 'a,
+    M1: FlaggedMom<'a, ZUniqHType = Z, ValueType = V> + ?Sized,
+    M2: FlaggedMom<'a, ZUniqHType = Z, ValueType = V> + ?Sized,
+    F: FnMut(Option<(&V, Flag)>, Option<(&V, Flag)>) -> Option<(V, Flag)>,
+{
+  let depth_max = lhs.depth_max().max(rhs.depth_max());
+  let left = to_intervals_flagged(lhs, depth_max);
+  let right = to_intervals_flagged(rhs, depth_max);
+  let runs = sweep_flagged(&left, &right, op);
+  let mut entries = Vec::new();
+  for (start, end, value, flag) in runs {
+    let mut zuniqs = Vec::new();
+    append_zuniqs_for_range(depth_max, start, end, &mut zuniqs);
+    entries.extend(zuniqs.into_iter().map(|zuniq| (zuniq, value.clone(), flag)));
+  }
+  MomVecImplFlagged::new_unchecked(depth_max, entries)
+}
+
+/// Number of sibling cells sharing a same parent cell (`DIM*DIM`, see `ZUniqHashT::DIM`).
+const N_CHILDREN: usize = 4;
+
+/// Builder accepting arbitrary `(depth, hash, value)` pushes, in any order, and producing a
+/// valid, normalized `MomVecImpl` (sorted, non-overlapping, sibling cells merged).
+pub struct MomBuilder<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue,
+{
+  depth_max: u8,
+  entries: std::collections::BTreeMap<Z, V>,
+}
+
+impl<Z, V> MomBuilder<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue,
+{
+  /// Creates a new, empty builder for a MOM of maximum depth `depth_max`.
+  pub fn new(depth_max: u8) -> Self {
+    Self { depth_max, entries: std::collections::BTreeMap::new() }
+  }
+
+  /// Pushes the cell of given `depth` and `hash` with the given `value`.
+  /// Pushing twice the same cell overwrites the previously pushed value.
+  pub fn push(&mut self, depth: u8, hash: Z, value: V) -> &mut Self {
+    self.entries.insert(Z::to_zuniq(depth, hash), value);
+    self
+  }
+
+  /// Builds the normalized MOM: bottom-up, whenever all `N_CHILDREN` sibling cells at a given
+  /// depth are present and `merge` returns `Some(merged)` for their values, they get replaced by
+  /// their single parent cell carrying `merged`; this iterates until no further merge applies.
+  /// The result is checked using `Mom::check_is_mom`.
+  pub fn into_mom<F>(self, mut merge: F) -> Result<MomVecImpl<Z, V>, String>
+    where
+      F: FnMut(&[&V; N_CHILDREN]) -> Option<V>,
+  {
+    let mut entries: Vec<(Z, V)> = self.entries.into_iter().collect();
+    loop {
+      let (next, changed) = compact_siblings(entries, &mut merge);
+      entries = next;
+      if !changed {
+        break;
+      }
+    }
+    let mom = MomVecImpl::new_unchecked(self.depth_max, entries);
+    mom.check_is_mom()?;
+    Ok(mom)
+  }
+}
+
+/// Performs a single left-to-right pass over the sorted, non-overlapping `entries`, replacing
+/// every run of `N_CHILDREN` consecutive sibling cells for which `merge` returns `Some(_)` by
+/// their parent cell. Returns the (possibly shortened) entries and whether any merge occurred.
+fn compact_siblings<Z, V, F>(entries: Vec<(Z, V)>, merge: &mut F) -> (Vec<(Z, V)>, bool)
+  where
+    Z: ZUniqHashT,
+    F: FnMut(&[&V; N_CHILDREN]) -> Option<V>,
+{
+  let mut out: Vec<(Z, V)> = Vec::with_capacity(entries.len());
+  let mut changed = false;
+  let mut it = entries.into_iter().peekable();
+  while let Some((z, v)) = it.next() {
+    let (depth, hash) = Z::from_zuniq(z);
+    let n_children = Z::from(N_CHILDREN).unwrap();
+    if depth > 0 && hash % n_children == Z::zero() {
+      let parent_hash = hash / n_children;
+      let mut siblings = vec![v];
+      while siblings.len() < N_CHILDREN {
+        let is_next_sibling = matches!(it.peek(), Some(&(z2, _))
+          if Z::from_zuniq(z2) == (depth, parent_hash * n_children + Z::from(siblings.len()).unwrap()));
+        if !is_next_sibling {
+          break;
+        }
+        siblings.push(it.next().unwrap().1);
+      }
+      if siblings.len() == N_CHILDREN {
+        let refs: [&V; N_CHILDREN] = core::array::from_fn(|i| &siblings[i]);
+        if let Some(merged) = merge(&refs) {
+          out.push((Z::to_zuniq(depth - 1, parent_hash), merged));
+          changed = true;
+          continue;
+        }
+      }
+      out.extend(siblings.into_iter().enumerate()
+        .map(|(i, v)| (Z::to_zuniq(depth, parent_hash * n_children + Z::from(i).unwrap()), v)));
+    } else {
+      out.push((z, v));
+    }
+  }
+  (out, changed)
+}
+
+/// Builder accepting arbitrary `(depth, hash, value, flag)` pushes, in any order, and producing a
+/// valid, normalized `MomVecImplFlagged` (sorted, non-overlapping, sibling cells merged).
+/// Same bottom-up sibling-merging as `MomBuilder`, additionally degrading the parent cell's flag
+/// to `Flag::Partial` as soon as one of the `N_CHILDREN` merged children was `Partial`; the parent
+/// is `Flag::Full` only if all of them were `Full`.
+pub struct MomBuilderFlagged<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue,
+{
+  depth_max: u8,
+  entries: std::collections::BTreeMap<Z, (V, Flag)>,
+}
+
+impl<Z, V> MomBuilderFlagged<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue,
+{
+  /// Creates a new, empty builder for a flagged MOM of maximum depth `depth_max`.
+  pub fn new(depth_max: u8) -> Self {
+    Self { depth_max, entries: std::collections::BTreeMap::new() }
+  }
+
+  /// Pushes the cell of given `depth` and `hash` with the given `value` and `flag`.
+  /// Pushing twice the same cell overwrites the previously pushed value and flag.
+  pub fn push(&mut self, depth: u8, hash: Z, value: V, flag: Flag) -> &mut Self {
+    self.entries.insert(Z::to_zuniq(depth, hash), (value, flag));
+    self
+  }
+
+  /// Builds the normalized flagged MOM: bottom-up, whenever all `N_CHILDREN` sibling cells at a
+  /// given depth are present and `merge` returns `Some(merged)` for their values, they get
+  /// replaced by their single parent cell carrying `merged` and the degraded flag described in
+  /// this builder's doc comment; this iterates until no further merge applies. The result is
+  /// checked using `Mom::check_is_mom`.
+  pub fn into_mom<F>(self, mut merge: F) -> Result<MomVecImplFlagged<Z, V>, String>
+    where
+      F: FnMut(&[&V; N_CHILDREN]) -> Option<V>,
+  {
+    let mut entries: Vec<(Z, V, Flag)> =
+      self.entries.into_iter().map(|(z, (v, f))| (z, v, f)).collect();
+    loop {
+      let (next, changed) = compact_siblings_flagged(entries, &mut merge);
+      entries = next;
+      if !changed {
+        break;
+      }
+    }
+    let mom = MomVecImplFlagged::new_unchecked(self.depth_max, entries);
+    mom.check_is_mom()?;
+    Ok(mom)
+  }
+}
+
+/// Same as `compact_siblings`, additionally threading a `Flag` alongside each cell: a merged
+/// parent's flag is `Flag::Full` only if all `N_CHILDREN` merged children were `Full`
+/// (see `Flag::both_full`).
+fn compact_siblings_flagged<Z, V, F>(
+  entries: Vec<(Z, V, Flag)>,
+  merge: &mut F,
+) -> (Vec<(Z, V, Flag)>, bool)
+  where
+    Z: ZUniqHashT,
+    F: FnMut(&[&V; N_CHILDREN]) -> Option<V>,
+{
+  let mut out: Vec<(Z, V, Flag)> = Vec::with_capacity(entries.len());
+  let mut changed = false;
+  let mut it = entries.into_iter().peekable();
+  while let Some((z, v, flag)) = it.next() {
+    let (depth, hash) = Z::from_zuniq(z);
+    let n_children = Z::from(N_CHILDREN).unwrap();
+    if depth > 0 && hash % n_children == Z::zero() {
+      let parent_hash = hash / n_children;
+      let mut siblings = vec![(v, flag)];
+      while siblings.len() < N_CHILDREN {
+        let is_next_sibling = matches!(it.peek(), Some(&(z2, _, _))
+          if Z::from_zuniq(z2) == (depth, parent_hash * n_children + Z::from(siblings.len()).unwrap()));
+        if !is_next_sibling {
+          break;
+        }
+        let (_, v2, f2) = it.next().unwrap();
+        siblings.push((v2, f2));
+      }
+      if siblings.len() == N_CHILDREN {
+        let refs: [&V; N_CHILDREN] = core::array::from_fn(|i| &siblings[i].0);
+        if let Some(merged) = merge(&refs) {
+          let merged_flag = siblings.iter().map(|(_, f)| *f).reduce(Flag::both_full).unwrap();
+          out.push((Z::to_zuniq(depth - 1, parent_hash), merged, merged_flag));
+          changed = true;
+          continue;
+        }
+      }
+      out.extend(siblings.into_iter().enumerate()
+        .map(|(i, (v, f))| (Z::to_zuniq(depth, parent_hash * n_children + Z::from(i).unwrap()), v, f)));
+    } else {
+      out.push((z, v, flag));
+    }
+  }
+  (out, changed)
+}
+
+impl<Z, V> MomVecImpl<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: SkyMapValue,
+{
+  /// Builds an adaptive MOM from a full-resolution `skymap`, trading resolution for size:
+  /// starting at `skymap`'s maximum depth, groups of `N_CHILDREN` sibling cells are merged
+  /// bottom-up into their parent cell as long as `decision(depth, parent_hash, &values)`
+  /// returns `Some(merged_value)`; recursion stops, for a given branch, as soon as `decision`
+  /// returns `None`. Entries are emitted directly in z-order, so no post-sort is needed.
+  pub fn from_skymap_bottom_up<'a, S, F>(skymap: &'a S, mut decision: F) -> Self
+    where
+      S: SkyMap<'a, ValueType = V>,
+      V: Clone + 'a,
+      F: FnMut(u8, Z, &[&V; N_CHILDREN]) -> Option<V>,
+  {
+    let depth_max = skymap.depth();
+    let values: Vec<V> = skymap.values().cloned().collect();
+    let n_children = Z::from(N_CHILDREN).unwrap();
+    let mut entries = Vec::new();
+    for base in 0..Z::N_D0_CELLS {
+      let base_hash = Z::from(base).unwrap();
+      match build_skymap_node(depth_max, 0, base_hash, &values, n_children, &mut decision) {
+        SkyMapNode::Merged(v) => entries.push((Z::to_zuniq(0, base_hash), v)),
+        SkyMapNode::Split(es) => entries.extend(es),
+      }
+    }
+    Self::new_unchecked(depth_max, entries)
+  }
+}
+
+/// Intermediate result of `build_skymap_node`: either the subtree collapsed into a single,
+/// merged value, or it stayed split into several (already z-ordered) finer entries.
+enum SkyMapNode<Z, V> {
+  Merged(V),
+  Split(Vec<(Z, V)>),
+}
+
+/// Recursively builds the subtree rooted at cell `(depth, hash)`, down to `skymap_depth`
+/// (the skymap full resolution), bottom-up merging sibling leaves as long as `decision` allows it.
+fn build_skymap_node<Z, V, F>(
+  skymap_depth: u8,
+  depth: u8,
+  hash: Z,
+  values: &[V],
+  n_children: Z,
+  decision: &mut F,
+) -> SkyMapNode<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: Clone,
+    F: FnMut(u8, Z, &[&V; N_CHILDREN]) -> Option<V>,
+{
+  if depth == skymap_depth {
+    return SkyMapNode::Merged(values[hash.to_usize().unwrap()].clone());
+  }
+  let children: [SkyMapNode<Z, V>; N_CHILDREN] = core::array::from_fn(|i| {
+    build_skymap_node(skymap_depth, depth + 1, hash * n_children + Z::from(i).unwrap(), values, n_children, decision)
+  });
+  let merged = match &children {
+    [SkyMapNode::Merged(v0), SkyMapNode::Merged(v1), SkyMapNode::Merged(v2), SkyMapNode::Merged(v3)] =>
+      decision(depth, hash, &[v0, v1, v2, v3]),
+    _ => None,
+  };
+  if let Some(merged) = merged {
+    return SkyMapNode::Merged(merged);
+  }
+  let mut entries = Vec::with_capacity(N_CHILDREN);
+  for (i, child) in children.into_iter().enumerate() {
+    match child {
+      SkyMapNode::Merged(v) => entries.push((Z::to_zuniq(depth + 1, hash * n_children + Z::from(i).unwrap()), v)),
+      SkyMapNode::Split(es) => entries.extend(es),
+    }
+  }
+  SkyMapNode::Split(entries)
+}
+
+/// Size, in bytes, of a FITS header/data block.
+const FITS_BLOCK_SIZE: usize = 2880;
+/// Size, in bytes, of a single FITS header card.
+const FITS_CARD_SIZE: usize = 80;
+
+/// Value types a MOM `VALUE` column can hold when written to / read from a FITS file,
+/// as enumerated by the IVOA MOC BINTABLE convention.
+pub trait FitsMomValue: SkyMapValue + Sized {
+  /// FITS `TFORM` type code (see the FITS standard, e.g. `'B'`, `'I'`, `'J'`, `'K'`, `'E'`, `'D'`).
+  const TFORM: char;
+  /// Size, in bytes, of a single value in the BINTABLE.
+  const N_BYTES: usize;
+  /// Big-endian serialization of `self`, as stored in the BINTABLE.
+  fn to_be_bytes_vec(&self) -> Vec<u8>;
+  /// Reverse of `to_be_bytes_vec`: reads a value from its big-endian bytes
+  /// (which must contain exactly `Self::N_BYTES` bytes).
+  fn from_be_bytes_slice(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_fits_mom_value {
+  ($t:ty, $tform:expr) => {
+    impl FitsMomValue for $t {
+      const TFORM: char = $tform;
+      const N_BYTES: usize = mem::size_of::<$t>();
+      fn to_be_bytes_vec(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+      }
+      fn from_be_bytes_slice(bytes: &[u8]) -> Self {
+        let mut buf = [0u8; mem::size_of::<$t>()];
+        buf.copy_from_slice(bytes);
+        <$t>::from_be_bytes(buf)
+      }
+    }
+  };
+}
+impl_fits_mom_value!(u8, 'B');
+impl_fits_mom_value!(i16, 'I');
+impl_fits_mom_value!(i32, 'J');
+impl_fits_mom_value!(i64, 'K');
+impl_fits_mom_value!(f32, 'E');
+impl_fits_mom_value!(f64, 'D');
+
+/// A MOM read from a FITS file, typed according to its `VALUE` column `TFORM`.
+pub enum FitsMomElems<Z: ZUniqHashT> {
+  U8(MomVecImpl<Z, u8>),
+  I16(MomVecImpl<Z, i16>),
+  I32(MomVecImpl<Z, i32>),
+  I64(MomVecImpl<Z, i64>),
+  F32(MomVecImpl<Z, f32>),
+  F64(MomVecImpl<Z, f64>),
+}
+
+impl<Z: ZUniqHashT> FitsMomElems<Z> {
+  /// Reads a MOM from a FITS BINTABLE written by `MomVecImpl::to_fits`, returning the variant
+  /// matching the `VALUE` column actual type.
+  pub fn from_fits<R: Read>(mut reader: R) -> Result<Self, String> {
+    skip_fits_header(&mut reader).map_err(|e| e.to_string())?;
+    let header = FitsMomHeader::read(&mut reader)?;
+    match header.value_tform {
+      'B' => read_fits_rows(&mut reader, &header).map(FitsMomElems::U8),
+      'I' => read_fits_rows(&mut reader, &header).map(FitsMomElems::I16),
+      'J' => read_fits_rows(&mut reader, &header).map(FitsMomElems::I32),
+      'K' => read_fits_rows(&mut reader, &header).map(FitsMomElems::I64),
+      'E' => read_fits_rows(&mut reader, &header).map(FitsMomElems::F32),
+      'D' => read_fits_rows(&mut reader, &header).map(FitsMomElems::F64),
+      other => Err(format!("Unsupported MOM FITS VALUE TFORM: '{}'.", other)),
+    }
+  }
+}
+
+impl<Z, V> MomVecImpl<Z, V>
+  where
+    Z: ZUniqHashT,
+    V: FitsMomValue,
+{
+  /// Writes this MOM as a single-extension FITS file: a BINTABLE of `(UNIQ, VALUE)` rows with a
+  /// `MOCORDER`/`PIXTYPE= 'HEALPIX'`/`ORDERING= 'NUNIQ'` header, so the file interoperates with
+  /// the wider MOC ecosystem.
+  pub fn to_fits<W: Write>(&self, mut writer: W) -> Result<(), String> {
+    write_fits_primary_header(&mut writer).map_err(|e| e.to_string())?;
+    write_fits_bintable(&mut writer, self.depth, &self.entries).map_err(|e| e.to_string())
+  }
+}
+
+/// Parsed keywords of a MOM BINTABLE extension header, as needed to read back its rows.
+struct FitsMomHeader {
+  depth_max: u8,
+  n_rows: usize,
+  uniq_tform: char,
+  value_tform: char,
+}
+
+impl FitsMomHeader {
+  fn read<R: Read>(reader: &mut R) -> Result<Self, String> {
+    let cards = read_fits_header_cards(reader).map_err(|e| e.to_string())?;
+    let get = |key: &str| -> Result<&String, String> {
+      cards.get(key).ok_or_else(|| format!("Missing '{}' FITS keyword in MOM BINTABLE header.", key))
+    };
+    let pixtype = get("PIXTYPE")?.trim_matches('\'').trim().to_string();
+    if pixtype != "HEALPIX" {
+      return Err(format!("Unsupported MOM FITS PIXTYPE: '{}'. Expected: 'HEALPIX'.", pixtype));
+    }
+    let ordering = get("ORDERING")?.trim_matches('\'').trim().to_string();
+    if ordering != "NUNIQ" {
+      return Err(format!("Unsupported MOM FITS ORDERING: '{}'. Expected: 'NUNIQ'.", ordering));
+    }
+    let depth_max: u8 = get("MOCORDER")?.trim().parse()
+      .map_err(|_| "Invalid 'MOCORDER' FITS keyword value.".to_string())?;
+    let n_rows: usize = get("NAXIS2")?.trim().parse()
+      .map_err(|_| "Invalid 'NAXIS2' FITS keyword value.".to_string())?;
+    let tform1 = get("TFORM1")?.trim_matches('\'').trim().to_string();
+    let uniq_tform = tform1.chars().last()
+      .ok_or_else(|| "Empty 'TFORM1' FITS keyword value.".to_string())?;
+    let tform2 = get("TFORM2")?.trim_matches('\'').trim().to_string();
+    let value_tform = tform2.chars().last()
+      .ok_or_else(|| "Empty 'TFORM2' FITS keyword value.".to_string())?;
+    Ok(Self { depth_max, n_rows, uniq_tform, value_tform })
+  }
+}
+
+/// Reads `(UNIQ, VALUE)` rows following a MOM BINTABLE header and builds the resulting
+/// `MomVecImpl`, checking the result is a valid MOM (see `Mom::check_is_mom`).
+fn read_fits_rows<Z, V, R>(reader: &mut R, header: &FitsMomHeader) -> Result<MomVecImpl<Z, V>, String>
+  where
+    Z: ZUniqHashT,
+    V: FitsMomValue,
+    R: Read,
+{
+  let expected_uniq_tform = if Z::N_BYTES == 4 { 'J' } else { 'K' };
+  if header.uniq_tform != expected_uniq_tform {
+    return Err(format!(
+      "UNIQ column TFORM1 ('1{}') does not match the requested hash type ({}-byte, expected '1{}').",
+      header.uniq_tform, Z::N_BYTES, expected_uniq_tform
+    ));
+  }
+  let mut uniq_buf = vec![0u8; Z::N_BYTES as usize];
+  let mut value_buf = vec![0u8; V::N_BYTES];
+  let mut entries = Vec::with_capacity(header.n_rows);
+  for _ in 0..header.n_rows {
+    reader.read_exact(&mut uniq_buf).map_err(|e| e.to_string())?;
+    reader.read_exact(&mut value_buf).map_err(|e| e.to_string())?;
+    let (depth, hash) = Z::from_uniq(Z::from_be_bytes_slice(&uniq_buf));
+    entries.push((Z::to_zuniq(depth, hash), V::from_be_bytes_slice(&value_buf)));
+  }
+  // The IVOA MOC convention sorts rows by UNIQ, not by our sort-friendly `zuniq`: e.g. the
+  // depth-0 cell of UNIQ 5 comes before the depth-1 cell of UNIQ 16 in a standard file, but
+  // `to_zuniq(0, 1) > to_zuniq(1, 0)`. Re-sort before validating so standard MOC files are accepted.
+  entries.sort_by_key(|&(zuniq, _)| zuniq);
+  let mom = MomVecImpl::new_unchecked(header.depth_max, entries);
+  mom.check_is_mom()?;
+  Ok(mom)
+}
+
+/// Writes a minimal, empty-data primary FITS HDU (the MOM itself is written in the following
+/// BINTABLE extension), as required by the FITS standard.
+fn write_fits_primary_header<W: Write>(writer: &mut W) -> io::Result<()> {
+  let mut header = Vec::new();
+  write_fits_card(&mut header, "SIMPLE", "T", None);
+  write_fits_card(&mut header, "BITPIX", "8", None);
+  write_fits_card(&mut header, "NAXIS", "0", None);
+  write_fits_card(&mut header, "EXTEND", "T", None);
+  write_fits_end_card(&mut header);
+  writer.write_all(&header)
+}
+
+/// Writes the MOM `(UNIQ, VALUE)` entries as a BINTABLE extension.
+fn write_fits_bintable<Z, V, W>(writer: &mut W, depth_max: u8, entries: &[(Z, V)]) -> io::Result<()>
+  where
+    Z: ZUniqHashT,
+    V: FitsMomValue,
+    W: Write,
+{
+  let row_bytes = Z::N_BYTES as usize + V::N_BYTES;
+  let mut header = Vec::new();
+  write_fits_card(&mut header, "XTENSION", "'BINTABLE'", None);
+  write_fits_card(&mut header, "BITPIX", "8", None);
+  write_fits_card(&mut header, "NAXIS", "2", None);
+  write_fits_card(&mut header, "NAXIS1", &row_bytes.to_string(), Some("Bytes per row"));
+  write_fits_card(&mut header, "NAXIS2", &entries.len().to_string(), Some("Number of rows"));
+  write_fits_card(&mut header, "PCOUNT", "0", None);
+  write_fits_card(&mut header, "GCOUNT", "1", None);
+  write_fits_card(&mut header, "TFIELDS", "2", None);
+  write_fits_card(&mut header, "TTYPE1", "'UNIQ'", None);
+  write_fits_card(&mut header, "TFORM1", &format!("'1{}'", if Z::N_BYTES == 4 { 'J' } else { 'K' }), None);
+  write_fits_card(&mut header, "TTYPE2", "'VALUE'", None);
+  write_fits_card(&mut header, "TFORM2", &format!("'1{}'", V::TFORM), None);
+  write_fits_card(&mut header, "MOCORDER", &depth_max.to_string(), Some("Maximum depth of the MOM"));
+  write_fits_card(&mut header, "PIXTYPE", "'HEALPIX'", None);
+  write_fits_card(&mut header, "ORDERING", "'NUNIQ'", None);
+  write_fits_end_card(&mut header);
+  writer.write_all(&header)?;
+
+  let mut data = Vec::with_capacity(entries.len() * row_bytes);
+  for (zuniq, value) in entries {
+    let (depth, hash) = Z::from_zuniq(*zuniq);
+    data.extend_from_slice(&Z::to_uniq(depth, hash).to_be_bytes_vec());
+    data.extend_from_slice(&value.to_be_bytes_vec());
   }
+  pad_fits_block(&mut data, 0);
+  writer.write_all(&data)
 }
 
+/// Appends an `END` card and pads `header` with spaces up to a multiple of `FITS_BLOCK_SIZE`.
+fn write_fits_end_card(header: &mut Vec<u8>) {
+  let mut card = vec![b' '; FITS_CARD_SIZE];
+  card[0..3].copy_from_slice(b"END");
+  header.extend_from_slice(&card);
+  pad_fits_block(header, b' ');
+}
 
-/*
+/// Pads `buf` with `fill` bytes up to a multiple of `FITS_BLOCK_SIZE`.
+fn pad_fits_block(buf: &mut Vec<u8>, fill: u8) {
+  let rem = buf.len() % FITS_BLOCK_SIZE;
+  if rem != 0 {
+    buf.resize(buf.len() + (FITS_BLOCK_SIZE - rem), fill);
+  }
+}
+
+/// Writes a single 80-byte FITS header card: `KEYWORD = value / comment`.
+fn write_fits_card(header: &mut Vec<u8>, keyword: &str, value: &str, comment: Option<&str>) {
+  // Per the FITS standard, CHARACTER values are left-justified in the 11-30 value field (opening
+  // quote in column 11), while numeric/logical values are right-justified.
+  let field = if value.starts_with('\'') {
+    format!("{:<20}", value)
+  } else {
+    format!("{:>20}", value)
+  };
+  let mut card = format!("{:<8}= {}", keyword, field);
+  if let Some(comment) = comment {
+    card.push_str(" / ");
+    card.push_str(comment);
+  }
+  let mut bytes = card.into_bytes();
+  bytes.truncate(FITS_CARD_SIZE);
+  bytes.resize(FITS_CARD_SIZE, b' ');
+  header.extend_from_slice(&bytes);
+}
 
-pub struct Mom {
-  pub depth_max: u8,
-  pub elems: MomElems,
+/// Reads FITS header blocks until (and including) the `END` card and discards the parsed
+/// keywords (used to skip the primary header).
+fn skip_fits_header<R: Read>(reader: &mut R) -> io::Result<()> {
+  read_fits_header_cards(reader).map(|_| ())
 }
 
-pub enum FitsMomElems {
-  U64U8(Vec<(u64, u8)>),
-  U64I16(Vec<(u64, i16)>),
-  U64I32(Vec<(u64, i32)>),
-  U64I64(Vec<(u64, i64)>),
-  U64F32(Vec<(u64, f32)>),
-  U64F64(Vec<(u64, f64)>),
+/// Reads FITS header blocks until (and including) the `END` card, returning the parsed
+/// `KEYWORD -> value` (still-quoted, trimmed) associations.
+fn read_fits_header_cards<R: Read>(reader: &mut R) -> io::Result<HashMap<String, String>> {
+  let mut cards = HashMap::new();
+  let mut block = vec![0u8; FITS_BLOCK_SIZE];
+  'blocks: loop {
+    reader.read_exact(&mut block)?;
+    for card in block.chunks_exact(FITS_CARD_SIZE) {
+      let card = String::from_utf8_lossy(card);
+      let keyword = card[0..8].trim().to_string();
+      if keyword == "END" {
+        break 'blocks;
+      }
+      if let Some(eq_pos) = card.find('=') {
+        let value = card[eq_pos + 1..].split('/').next().unwrap_or("").trim().to_string();
+        cards.insert(keyword, value);
+      }
+    }
+  }
+  Ok(cards)
 }
 
-*/
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_check_is_mom_rejects_depth_exceeding_max() {
+    // Single entry, at depth 1, while the MOM claims a maximum depth of 0: must be rejected,
+    // including when it is both the first and the last (i.e. only) element.
+    let mom = MomVecImpl::new_unchecked(0, vec![(u32::to_zuniq(1, 0), 1u8)]);
+    assert!(mom.check_is_mom().is_err());
+    // Same check, but for the last element of a longer, otherwise valid, MOM.
+    let mom = MomVecImpl::new_unchecked(
+      0,
+      vec![(u32::to_zuniq(0, 0), 1u8), (u32::to_zuniq(1, 4), 2u8)],
+    );
+    assert!(mom.check_is_mom().is_err());
+  }
+
+  #[test]
+  fn test_check_is_mom_accepts_valid_mom() {
+    let mom = MomVecImpl::new_unchecked(
+      1,
+      vec![(u32::to_zuniq(0, 1), 1u8), (u32::to_zuniq(1, 8), 2u8)],
+    );
+    assert!(mom.check_is_mom().is_ok());
+  }
+
+  #[test]
+  fn test_or_combines_overlapping_and_disjoint_cells_at_differing_depth_max() {
+    // `lhs`, depth_max = 0: base cells 0 and 1.
+    let lhs = MomVecImpl::new_unchecked(
+      0,
+      vec![(u32::to_zuniq(0, 0), 1i32), (u32::to_zuniq(0, 1), 2i32)],
+    );
+    // `rhs`, depth_max = 1: base cell 0 split into its 4 children, base cell 2 untouched.
+    let rhs = MomVecImpl::new_unchecked(
+      1,
+      vec![
+        (u32::to_zuniq(1, 0), 10i32),
+        (u32::to_zuniq(1, 1), 20i32),
+        (u32::to_zuniq(1, 2), 30i32),
+        (u32::to_zuniq(1, 3), 40i32),
+        (u32::to_zuniq(0, 2), 100i32),
+      ],
+    );
+    let union = lhs.or(&rhs, |l, r| l.copied().unwrap_or(0) + r.copied().unwrap_or(0));
+    assert!(union.check_is_mom().is_ok());
+    assert_eq!(union.depth_max(), 1);
+    let entries: Vec<_> = union.entries().map(|(z, v)| (z, *v)).collect();
+    assert_eq!(
+      entries,
+      vec![
+        (u32::to_zuniq(1, 0), 11i32),
+        (u32::to_zuniq(1, 1), 21i32),
+        (u32::to_zuniq(1, 2), 31i32),
+        (u32::to_zuniq(1, 3), 41i32),
+        (u32::to_zuniq(0, 1), 2i32),
+        (u32::to_zuniq(0, 2), 100i32),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_and_keeps_only_overlapping_cells() {
+    let lhs = MomVecImpl::new_unchecked(0, vec![(u32::to_zuniq(0, 0), 1i32)]);
+    let rhs = MomVecImpl::new_unchecked(
+      1,
+      vec![(u32::to_zuniq(1, 1), 10i32), (u32::to_zuniq(0, 1), 20i32)],
+    );
+    let inter = lhs.and(&rhs, |l, r| l.copied().unwrap_or(0) + r.copied().unwrap_or(0));
+    let entries: Vec<_> = inter.entries().map(|(z, v)| (z, *v)).collect();
+    assert_eq!(entries, vec![(u32::to_zuniq(1, 1), 11i32)]);
+  }
+
+  #[test]
+  fn test_not_and_xor() {
+    let lhs = MomVecImpl::new_unchecked(
+      0,
+      vec![(u32::to_zuniq(0, 0), 1i32), (u32::to_zuniq(0, 1), 2i32)],
+    );
+    let rhs = MomVecImpl::new_unchecked(0, vec![(u32::to_zuniq(0, 0), 10i32)]);
+
+    let diff = lhs.not(&rhs);
+    let entries: Vec<_> = diff.entries().map(|(z, v)| (z, *v)).collect();
+    assert_eq!(entries, vec![(u32::to_zuniq(0, 1), 2i32)]);
+
+    let sym_diff = lhs.xor(&rhs);
+    let entries: Vec<_> = sym_diff.entries().map(|(z, v)| (z, *v)).collect();
+    assert_eq!(entries, vec![(u32::to_zuniq(0, 1), 2i32)]);
+  }
+
+  #[test]
+  fn test_fits_write_read_round_trip() {
+    let mom = MomVecImpl::<u32, u8>::new_unchecked(
+      1,
+      vec![
+        (u32::to_zuniq(1, 0), 7u8),
+        (u32::to_zuniq(0, 1), 9u8),
+        (u32::to_zuniq(0, 2), 11u8),
+      ],
+    );
+    let mut buf = Vec::new();
+    mom.to_fits(&mut buf).unwrap();
+    match FitsMomElems::<u32>::from_fits(&buf[..]).unwrap() {
+      FitsMomElems::U8(read_back) => {
+        assert_eq!(read_back.depth_max(), 1);
+        let entries: Vec<_> = read_back.entries().map(|(z, v)| (z, *v)).collect();
+        assert_eq!(
+          entries,
+          vec![
+            (u32::to_zuniq(1, 0), 7u8),
+            (u32::to_zuniq(0, 1), 9u8),
+            (u32::to_zuniq(0, 2), 11u8),
+          ]
+        );
+      }
+      _ => panic!("expected the U8 FitsMomElems variant"),
+    }
+  }
+
+  #[test]
+  fn test_fits_reads_rows_sorted_by_uniq_rather_than_zuniq() {
+    // Standard MOC FITS files are sorted by ascending UNIQ, not by our internal zuniq: e.g. the
+    // depth-0 cell of hash 1 (uniq=5) precedes the depth-1 cell of hash 0 (uniq=16), while
+    // `to_zuniq(0, 1) > to_zuniq(1, 0)`, i.e. the reverse zuniq order. Build such a fixture by
+    // hand and check it is still accepted and correctly reordered.
+    let rows_in_uniq_order: Vec<(u8, u32, u8)> = vec![(0, 1, 9), (1, 0, 7)];
+
+    let mut buf = Vec::new();
+    write_fits_primary_header(&mut buf).unwrap();
+    let mut header = Vec::new();
+    write_fits_card(&mut header, "XTENSION", "'BINTABLE'", None);
+    write_fits_card(&mut header, "BITPIX", "8", None);
+    write_fits_card(&mut header, "NAXIS", "2", None);
+    write_fits_card(&mut header, "NAXIS1", "5", None);
+    write_fits_card(&mut header, "NAXIS2", &rows_in_uniq_order.len().to_string(), None);
+    write_fits_card(&mut header, "PCOUNT", "0", None);
+    write_fits_card(&mut header, "GCOUNT", "1", None);
+    write_fits_card(&mut header, "TFIELDS", "2", None);
+    write_fits_card(&mut header, "TTYPE1", "'UNIQ'", None);
+    write_fits_card(&mut header, "TFORM1", "'1J'", None);
+    write_fits_card(&mut header, "TTYPE2", "'VALUE'", None);
+    write_fits_card(&mut header, "TFORM2", "'1B'", None);
+    write_fits_card(&mut header, "MOCORDER", "1", None);
+    write_fits_card(&mut header, "PIXTYPE", "'HEALPIX'", None);
+    write_fits_card(&mut header, "ORDERING", "'NUNIQ'", None);
+    write_fits_end_card(&mut header);
+    buf.extend_from_slice(&header);
+    let mut data = Vec::new();
+    for (depth, hash, value) in rows_in_uniq_order {
+      data.extend_from_slice(&u32::to_uniq(depth, hash).to_be_bytes_vec());
+      data.push(value);
+    }
+    pad_fits_block(&mut data, 0);
+    buf.extend_from_slice(&data);
+
+    match FitsMomElems::<u32>::from_fits(&buf[..]).unwrap() {
+      FitsMomElems::U8(mom) => {
+        assert!(mom.check_is_mom().is_ok());
+        let entries: Vec<_> = mom.entries().map(|(z, v)| (z, *v)).collect();
+        assert_eq!(entries, vec![(u32::to_zuniq(1, 0), 7u8), (u32::to_zuniq(0, 1), 9u8)]);
+      }
+      _ => panic!("expected the U8 FitsMomElems variant"),
+    }
+  }
+
+  #[test]
+  fn test_fits_rejects_uniq_width_mismatch() {
+    let mom = MomVecImpl::<u32, u8>::new_unchecked(0, vec![(u32::to_zuniq(0, 0), 1u8)]);
+    let mut buf = Vec::new();
+    mom.to_fits(&mut buf).unwrap();
+    // Written with a `u32` (4-byte) UNIQ column; reading it back as `u64` must fail loudly
+    // instead of silently desyncing every row.
+    match FitsMomElems::<u64>::from_fits(&buf[..]) {
+      Err(e) => assert!(e.contains("TFORM1")),
+      Ok(_) => panic!("expected a TFORM1/hash type mismatch error"),
+    }
+  }
+
+  #[test]
+  fn test_mom_builder_merges_siblings_with_equal_values_only() {
+    let mut builder = MomBuilder::<u32, i32>::new(2);
+    // Depth-1 cell of hash 0: its 4 depth-2 children all carry the same value, so they collapse
+    // into a single depth-1 entry.
+    for i in 0..4u32 {
+      builder.push(2, i, 5);
+    }
+    // Depth-1 cell of hash 1: its 4 depth-2 children carry differing values, so `merge` refuses
+    // and they stay split.
+    builder.push(2, 4, 1);
+    builder.push(2, 5, 2);
+    builder.push(2, 6, 3);
+    builder.push(2, 7, 4);
+
+    let mom = builder.into_mom(|children| {
+      if children.iter().all(|v| **v == *children[0]) {
+        Some(*children[0])
+      } else {
+        None
+      }
+    }).unwrap();
+
+    assert!(mom.check_is_mom().is_ok());
+    let entries: Vec<_> = mom.entries().map(|(z, v)| (z, *v)).collect();
+    assert_eq!(
+      entries,
+      vec![
+        (u32::to_zuniq(1, 0), 5),
+        (u32::to_zuniq(2, 4), 1),
+        (u32::to_zuniq(2, 5), 2),
+        (u32::to_zuniq(2, 6), 3),
+        (u32::to_zuniq(2, 7), 4),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_mom_builder_merges_recursively_up_to_the_root() {
+    // All 12 base cells, each fully split down to depth 2 with the very same value, must collapse
+    // all the way back up to their depth-0 parent.
+    let mut builder = MomBuilder::<u32, i32>::new(2);
+    for base in 0..12u32 {
+      for i in 0..16u32 {
+        builder.push(2, base * 16 + i, 7);
+      }
+    }
+    let mom = builder.into_mom(|children| {
+      if children.iter().all(|v| **v == *children[0]) {
+        Some(*children[0])
+      } else {
+        None
+      }
+    }).unwrap();
+    assert!(mom.check_is_mom().is_ok());
+    let entries: Vec<_> = mom.entries().map(|(z, v)| (z, *v)).collect();
+    assert_eq!(entries, (0..12u32).map(|base| (u32::to_zuniq(0, base), 7)).collect::<Vec<_>>());
+  }
+
+  /// Minimal, flat-`Vec`-backed `SkyMap`, just enough to exercise `from_skymap_bottom_up`.
+  struct VecSkyMap<V> {
+    depth: u8,
+    values: Vec<V>,
+  }
+  impl<'a, V: SkyMapValue + 'a> SkyMap<'a> for VecSkyMap<V> {
+    type ValueType = V;
+    type ValuesIt = std::slice::Iter<'a, V>;
+    fn depth(&self) -> u8 {
+      self.depth
+    }
+    fn values(&'a self) -> Self::ValuesIt {
+      self.values.iter()
+    }
+  }
+
+  #[test]
+  fn test_from_skymap_bottom_up_merges_only_uniform_siblings() {
+    // Base cell 0's 4 depth-1 children all carry the same value: they must collapse into a
+    // single depth-0 entry. Every other base cell's children carry distinct values (their own
+    // hash), so `decision` refuses and they stay split at depth 1.
+    let values: Vec<i32> = (0..48).map(|i| if i < 4 { 100 } else { i as i32 }).collect();
+    let skymap = VecSkyMap { depth: 1, values };
+    let mom = MomVecImpl::<u32, i32>::from_skymap_bottom_up(&skymap, |_depth, _hash, children| {
+      if children.iter().all(|v| **v == *children[0]) {
+        Some(*children[0])
+      } else {
+        None
+      }
+    });
+    assert!(mom.check_is_mom().is_ok());
+    let entries: Vec<_> = mom.entries().map(|(z, v)| (z, *v)).collect();
+    let mut expected = vec![(u32::to_zuniq(0, 0), 100)];
+    expected.extend((4..48u32).map(|hash| (u32::to_zuniq(1, hash), hash as i32)));
+    assert_eq!(entries, expected);
+  }
+
+  #[test]
+  fn test_flagged_mom_builder_degrades_flag_unless_all_children_full() {
+    let merge = |children: &[&i32; N_CHILDREN]| {
+      if children.iter().all(|v| **v == *children[0]) {
+        Some(*children[0])
+      } else {
+        None
+      }
+    };
+
+    // Depth-1 cell of hash 0: all 4 depth-2 children are `Full`, so the merged parent is `Full`.
+    let mut all_full = MomBuilderFlagged::<u32, i32>::new(2);
+    for i in 0..4u32 {
+      all_full.push(2, i, 1, Flag::Full);
+    }
+    let mom = all_full.into_mom(merge).unwrap();
+    assert_eq!(mom.entries_flagged().collect::<Vec<_>>(), vec![(u32::to_zuniq(1, 0), &1, Flag::Full)]);
+
+    // Same values, but one child is `Partial`: the merged parent must be forced to `Partial`.
+    let mut one_partial = MomBuilderFlagged::<u32, i32>::new(2);
+    one_partial.push(2, 0, 1, Flag::Full);
+    one_partial.push(2, 1, 1, Flag::Partial);
+    one_partial.push(2, 2, 1, Flag::Full);
+    one_partial.push(2, 3, 1, Flag::Full);
+    let mom = one_partial.into_mom(merge).unwrap();
+    assert_eq!(mom.entries_flagged().collect::<Vec<_>>(), vec![(u32::to_zuniq(1, 0), &1, Flag::Partial)]);
+  }
+
+  #[test]
+  fn test_mom_slice_impl_get_overlapped_cells_no_underflow_at_start() {
+    // Regression test: a query cell overlapped by the very first entry must not underflow
+    // `range.start - 1` while walking left.
+    let entries = [(u32::to_zuniq(0, 0), 1i32), (u32::to_zuniq(0, 1), 2i32)];
+    let mom = MomSliceImpl::new_unchecked(1, &entries);
+    let overlapped = mom.get_overlapped_cells(u32::to_zuniq(1, 0));
+    assert_eq!(overlapped, vec![(u32::to_zuniq(0, 0), &1i32)]);
+  }
+
+  #[test]
+  fn test_mom_slice_impl_matches_mom_vec_impl() {
+    let entries = vec![(u32::to_zuniq(0, 0), 1i32), (u32::to_zuniq(0, 1), 2i32)];
+    let vec_mom = MomVecImpl::new_unchecked(0, entries.clone());
+    let slice_mom = MomSliceImpl::new_unchecked(0, &entries);
+    assert_eq!(
+      vec_mom.entries().collect::<Vec<_>>(),
+      slice_mom.entries().collect::<Vec<_>>()
+    );
+    assert_eq!(
+      vec_mom.get_cell_containing_unsafe(u32::to_zuniq(0, 1)).map(|(z, v)| (z, *v)),
+      slice_mom.get_cell_containing_unsafe(u32::to_zuniq(0, 1)).map(|(z, v)| (z, *v))
+    );
+  }
+}